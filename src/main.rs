@@ -1,11 +1,130 @@
-use serde_json::{ Value, from_str };
+use regex::Regex;
+use serde_json::{ json, from_str, Value };
+use sha1::Sha1;
+use sha2::{ Digest, Sha256, Sha512 };
+use std::collections::HashMap;
 use std::fs::read_to_string;
-use std::io::Error;
-use std::path::Path;
+use std::io::{ Error, Read };
+use std::path::{ Path, PathBuf };
 use std::process::ExitCode;
 
-fn read_package_lock(dir: &str) -> Result<String, Error> {
-    read_to_string(Path::new(dir).join("package-lock.json"))
+/// Which of the required fields was absent on a package entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Missing {
+    Resolved,
+    Integrity,
+    Both,
+}
+
+impl Missing {
+    /// The individual field names this covers, as they appear in the lock file.
+    fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Missing::Resolved => &["resolved"],
+            Missing::Integrity => &["integrity"],
+            Missing::Both => &["resolved", "integrity"],
+        }
+    }
+}
+
+/// What kind of problem a finding represents.
+#[derive(Debug)]
+enum FindingKind {
+    /// A required field (`resolved` / `integrity`) was absent.
+    Missing(Missing),
+    /// The recorded `integrity` did not match the fetched tarball.
+    IntegrityMismatch { expected: String, actual: String },
+    /// The integrity could not be verified: the tarball could not be fetched,
+    /// or the recorded value is malformed or uses an unsupported algorithm.
+    FetchError { reason: String },
+    /// A user-defined rule failed for the matched node.
+    RuleViolation { rule: String },
+}
+
+impl FindingKind {
+    /// Short, stable category label used in the machine-readable output.
+    fn category(&self) -> &'static str {
+        match self {
+            FindingKind::Missing(_) => "missing",
+            FindingKind::IntegrityMismatch { .. } => "integrity mismatch",
+            FindingKind::FetchError { .. } => "fetch error",
+            FindingKind::RuleViolation { .. } => "rule violation",
+        }
+    }
+}
+
+/// A single problem found in a lock file.
+#[derive(Debug)]
+struct Finding {
+    /// Where the problem is: the `node_modules/...` package key, a dotted
+    /// `foo > bar` path in a lockfileVersion 1 tree, or the JSONPath of the
+    /// node a rule matched.
+    package: String,
+    kind: FindingKind,
+    severity: &'static str,
+}
+
+/// The machine-readable output format selected on the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    Sarif,
+}
+
+impl Format {
+    fn parse(s: &str) -> Option<Format> {
+        match s {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "sarif" => Some(Format::Sarif),
+            _ => None,
+        }
+    }
+}
+
+/// Discover the `package-lock.json` files to lint under `root`.
+///
+/// A `root` that is itself a file is used directly. A directory yields its own
+/// `package-lock.json`, or — with `recursive` — every one beneath it, skipping
+/// nested `node_modules` directories.
+fn discover(root: &str, recursive: bool) -> Vec<PathBuf> {
+    let p = Path::new(root);
+
+    if p.is_file() {
+        return vec![p.to_path_buf()];
+    }
+
+    if !recursive {
+        let candidate = p.join("package-lock.json");
+        return if candidate.is_file() { vec![candidate] } else { Vec::new() };
+    }
+
+    let mut out = Vec::new();
+    walk(p, &mut out);
+    out
+}
+
+fn walk(dir: &Path, out: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = path.file_name().and_then(|n| n.to_str());
+
+        if path.is_dir() {
+            // never descend into installed dependencies
+            if name == Some("node_modules") {
+                continue;
+            }
+            walk(&path, out);
+        } else if name == Some("package-lock.json") {
+            out.push(path);
+        }
+    }
 }
 
 fn parse_json(data: String) -> Result<Value, Error> {
@@ -14,89 +133,868 @@ fn parse_json(data: String) -> Result<Value, Error> {
     Ok(value)
 }
 
-fn validate_json(json: &Value) -> Vec<String> {
+/// Locate the directory prefix for a workspace `member`.
+///
+/// npm records each workspace under `packages` twice: once by its on-disk
+/// path (e.g. `packages/foo`) and once as a `node_modules/foo` symlink whose
+/// `link` is `true` and whose `resolved` points back at that path. We accept
+/// either the member name or its path and return the path prefix its nested
+/// dependencies live under.
+fn find_member_dir(json: &Value, member: &str) -> Option<String> {
+    let packages = json["packages"].as_object()?;
+
+    for (k, v) in packages {
+        if k.is_empty() {
+            continue;
+        }
+
+        // the member's own path key was given directly
+        if k == member {
+            return Some(member.to_string());
+        }
+
+        let base = k.rsplit('/').next().unwrap_or(k);
+
+        // the node_modules symlink npm creates for the workspace
+        if v["link"] == Value::Bool(true) && base == member {
+            if let Some(target) = v["resolved"].as_str() {
+                return Some(target.to_string());
+            }
+            return Some(k.to_string());
+        }
+
+        // a workspace path entry whose final segment is the member name
+        if !k.starts_with("node_modules") && base == member {
+            return Some(k.to_string());
+        }
+    }
+
+    None
+}
+
+/// Validate the flat `packages` map used by lockfileVersion 2/3.
+fn validate_packages(json: &Value, scope: Option<&str>) -> Vec<Finding> {
     json["packages"]
         .as_object()
-        .and_then(|packages| {
-            Some(
-                packages
-                    .iter()
-                    .filter_map(|(k, v)| {
-                        // empty string is self
-                        if k.is_empty() {
-                            return None;
-                        }
+        .map(|packages| {
+            packages
+                .iter()
+                .filter_map(|(k, v)| {
+                    // empty string is self
+                    if k.is_empty() {
+                        return None;
+                    }
 
-                        // if it's not a node module, then it's possible a workspace
-                        if !k.starts_with("node_modules") {
-                            return None;
-                        }
+                    // Restrict to the selected workspace, or to top-level node
+                    // modules when no member was chosen.
+                    //
+                    // NOTE: this only covers deps that nest under the member
+                    // (i.e. version-conflicted ones npm could not hoist). A
+                    // member's hoisted closure lives in the root `node_modules`
+                    // and cannot be attributed to a single member without
+                    // resolving the dependency graph, so it is left to a
+                    // non-scoped run. See chunk0-2 review.
+                    let in_scope = match scope {
+                        Some(dir) => k.starts_with(&format!("{}/node_modules", dir)),
+                        None => k.starts_with("node_modules"),
+                    };
+                    if !in_scope {
+                        return None;
+                    }
+
+                    // symlink is fine
+                    if v["link"] == Value::Bool(true) {
+                        return None;
+                    }
+
+                    // missing integrity / missing resolved
+                    missing_of(v).map(|m| Finding {
+                        package: k.to_string(),
+                        kind: FindingKind::Missing(m),
+                        severity: "error",
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Which of `resolved`/`integrity` is absent on a package node, if any.
+fn missing_of(v: &Value) -> Option<Missing> {
+    let no_resolved = v["resolved"] == Value::Null;
+    let no_integrity = v["integrity"] == Value::Null;
+
+    match (no_resolved, no_integrity) {
+        (true, true) => Some(Missing::Both),
+        (true, false) => Some(Missing::Resolved),
+        (false, true) => Some(Missing::Integrity),
+        (false, false) => None,
+    }
+}
+
+/// Recursively walk the nested `dependencies` tree of a lockfileVersion 1
+/// lock file, reporting missing fields with a dotted `foo > bar > baz` path.
+///
+/// `bundled` dependencies ship inside their parent's tarball and carry no
+/// `resolved`/`integrity` of their own, so they're always skipped; `dev`
+/// dependencies are skipped only when `skip_dev` is set.
+fn validate_dependencies(
+    deps: &serde_json::Map<String, Value>,
+    skip_dev: bool,
+    prefix: &str,
+    findings: &mut Vec<Finding>,
+) {
+    for (name, node) in deps {
+        if node["bundled"] == Value::Bool(true) {
+            continue;
+        }
+        if skip_dev && node["dev"] == Value::Bool(true) {
+            continue;
+        }
+
+        let path = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{} > {}", prefix, name)
+        };
+
+        if let Some(m) = missing_of(node) {
+            findings.push(Finding {
+                package: path.clone(),
+                kind: FindingKind::Missing(m),
+                severity: "error",
+            });
+        }
+
+        if let Some(nested) = node["dependencies"].as_object() {
+            validate_dependencies(nested, skip_dev, &path, findings);
+        }
+    }
+}
+
+/// Validate a lock file, dispatching on its layout.
+///
+/// lockfileVersion 2/3 use the flat `packages` map; version 1 (or any lock
+/// file without a `packages` key) uses the recursively nested `dependencies`
+/// tree instead.
+fn validate_json(json: &Value, scope: Option<&str>, skip_dev: bool) -> Vec<Finding> {
+    if json["packages"].is_object() {
+        return validate_packages(json, scope);
+    }
+
+    if let Some(deps) = json["dependencies"].as_object() {
+        let mut findings = Vec::new();
+        validate_dependencies(deps, skip_dev, "", &mut findings);
+        return findings;
+    }
 
-                        // symlink is fine
-                        if v["link"] == Value::Bool(true) {
-                            return None;
+    Vec::new()
+}
+
+/// A single step in a (small subset of) JSONPath expression.
+#[derive(Debug)]
+enum Seg {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// The condition a rule asserts about the nodes its selector matches.
+#[derive(Debug)]
+enum Assertion {
+    Exists,
+    Equals(Value),
+    Matches(Regex),
+}
+
+/// A user-supplied validation rule: a JSONPath selector plus a condition.
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    segs: Vec<Seg>,
+    assertion: Assertion,
+}
+
+/// Parse the supported JSONPath subset: `$`, `.key`, `.*`, `[n]`, `[*]`.
+fn parse_path(path: &str) -> Result<Vec<Seg>, String> {
+    let mut chars = path.chars().peekable();
+
+    if chars.next() != Some('$') {
+        return Err(format!("JSONPath must start with `$`: {}", path));
+    }
+
+    let mut segs = Vec::new();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '.' => {
+                chars.next();
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                    segs.push(Seg::Wildcard);
+                } else {
+                    let mut key = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c == '.' || c == '[' {
+                            break;
                         }
+                        key.push(c);
+                        chars.next();
+                    }
+                    if key.is_empty() {
+                        return Err(format!("empty key in JSONPath: {}", path));
+                    }
+                    segs.push(Seg::Key(key));
+                }
+            }
+            '[' => {
+                chars.next();
+                let mut inner = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    inner.push(c);
+                }
+                let inner = inner.trim().trim_matches('\'').trim_matches('"');
+                if inner == "*" {
+                    segs.push(Seg::Wildcard);
+                } else if let Ok(i) = inner.parse::<usize>() {
+                    segs.push(Seg::Index(i));
+                } else {
+                    segs.push(Seg::Key(inner.to_string()));
+                }
+            }
+            _ => return Err(format!("unexpected character `{}` in JSONPath: {}", c, path)),
+        }
+    }
+
+    Ok(segs)
+}
+
+/// Evaluate `segs` against `root`, returning every matched node with the
+/// concrete path (keys/indices resolved) that reached it.
+///
+/// A wildcard over an object skips the empty `""` key, which in a lock file is
+/// the self entry (the project's own root package). This mirrors the
+/// `validate_packages` "empty string is self, skip it" invariant so a rule like
+/// `$.packages[*].integrity` doesn't fire a spurious violation on the root.
+fn eval_path<'a>(root: &'a Value, segs: &[Seg]) -> Vec<(String, &'a Value)> {
+    let mut current: Vec<(String, &Value)> = vec![("$".to_string(), root)];
 
-                        // missing integrity
-                        // missing resolved
-                        if v["integrity"] == Value::Null || v["resolved"] == Value::Null {
-                            return Some(k.to_string());
+    for seg in segs {
+        let mut next = Vec::new();
+        for (path, node) in &current {
+            match seg {
+                Seg::Key(k) => {
+                    if let Some(v) = node.get(k) {
+                        next.push((format!("{}.{}", path, k), v));
+                    }
+                }
+                Seg::Index(i) => {
+                    if let Some(v) = node.get(i) {
+                        next.push((format!("{}[{}]", path, i), v));
+                    }
+                }
+                Seg::Wildcard => match node {
+                    Value::Object(map) => {
+                        for (k, v) in map {
+                            if k.is_empty() {
+                                continue;
+                            }
+                            next.push((format!("{}.{}", path, k), v));
                         }
+                    }
+                    Value::Array(arr) => {
+                        for (i, v) in arr.iter().enumerate() {
+                            next.push((format!("{}[{}]", path, i), v));
+                        }
+                    }
+                    _ => {}
+                },
+            }
+        }
+        current = next;
+    }
 
-                        None
-                    })
-                    .collect()
-            )
-        })
-        .or_else(|| Some(Vec::new()))
-        .unwrap()
+    current
 }
 
-fn main() -> ExitCode {
-    let args = std::env::args().collect::<Vec<String>>();
+/// Parse a rule config file into a compiled ruleset.
+///
+/// Each rule is `{ "name", "path", "assert" }` where `assert` is the string
+/// `"exists"` or an object `{ "equals": <value> }` / `{ "matches": <regex> }`.
+fn load_rules(path: &str) -> Result<Vec<Rule>, String> {
+    let data = read_to_string(path).map_err(|e| e.to_string())?;
+    let config: Value = from_str(&data).map_err(|e| e.to_string())?;
+
+    let raw = config["rules"]
+        .as_array()
+        .ok_or_else(|| "rule config must have a `rules` array".to_string())?;
+
+    let mut rules = Vec::new();
+    for rule in raw {
+        let name = rule["name"]
+            .as_str()
+            .ok_or_else(|| "rule is missing a string `name`".to_string())?
+            .to_string();
+        let selector = rule["path"]
+            .as_str()
+            .ok_or_else(|| format!("rule `{}` is missing a string `path`", name))?;
+        let segs = parse_path(selector)?;
+
+        let assert = &rule["assert"];
+        let assertion = if assert == "exists" {
+            Assertion::Exists
+        } else if !assert["equals"].is_null() {
+            Assertion::Equals(assert["equals"].clone())
+        } else if let Some(re) = assert["matches"].as_str() {
+            Assertion::Matches(Regex::new(re).map_err(|e| e.to_string())?)
+        } else {
+            return Err(format!("rule `{}` has an unknown `assert`", name));
+        };
+
+        rules.push(Rule { name, segs, assertion });
+    }
 
-    let cwd = if args.len() > 1 { &args[1] } else { "." };
+    Ok(rules)
+}
 
-    let package_read = read_package_lock(cwd);
+/// Evaluate every rule against the parsed lock file, one finding per failure.
+///
+/// `equals` and `matches` are checked only against the nodes a selector
+/// actually matches: if the selected field is simply absent there is no node,
+/// so the rule passes over that entry. Asserting a field is present is the job
+/// of the separate `exists` assertion — pair it with `matches`/`equals` when a
+/// field must both exist and satisfy a condition.
+fn apply_rules(root: &Value, rules: &[Rule]) -> Vec<Finding> {
+    let mut findings = Vec::new();
 
-    if let Err(_) = package_read {
-        let path = Path::new(cwd).join("package-lock.json");
+    for rule in rules {
+        match &rule.assertion {
+            // `exists` is checked against the parent set so an absent key on a
+            // matched parent is reported, rather than passing vacuously.
+            Assertion::Exists => {
+                let (parents, last) = rule.segs.split_at(rule.segs.len().saturating_sub(1));
+                for (path, node) in eval_path(root, parents) {
+                    let present = match last.first() {
+                        Some(Seg::Key(k)) => node.get(k).is_some(),
+                        Some(Seg::Index(i)) => node.get(i).is_some(),
+                        Some(Seg::Wildcard) | None => true,
+                    };
+                    if !present {
+                        findings.push(Finding {
+                            package: path,
+                            kind: FindingKind::RuleViolation { rule: rule.name.clone() },
+                            severity: "error",
+                        });
+                    }
+                }
+            }
+            Assertion::Equals(expected) => {
+                for (path, node) in eval_path(root, &rule.segs) {
+                    if node != expected {
+                        findings.push(Finding {
+                            package: path,
+                            kind: FindingKind::RuleViolation { rule: rule.name.clone() },
+                            severity: "error",
+                        });
+                    }
+                }
+            }
+            Assertion::Matches(re) => {
+                for (path, node) in eval_path(root, &rule.segs) {
+                    let ok = node.as_str().map(|s| re.is_match(s)).unwrap_or(false);
+                    if !ok {
+                        findings.push(Finding {
+                            package: path,
+                            kind: FindingKind::RuleViolation { rule: rule.name.clone() },
+                            severity: "error",
+                        });
+                    }
+                }
+            }
+        }
+    }
 
-        println!("[ERROR] Could not read package-lock.json at {}", path.display());
+    findings
+}
 
-        return ExitCode::FAILURE;
+/// Constant-time byte-slice comparison, so a mismatch can't be timed.
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
     }
+    diff == 0
+}
 
-    let data = package_read.unwrap();
-    let parsed = parse_json(data);
+/// Recompute the base64 SRI digest of `bytes` under `alg`.
+///
+/// Returns `None` for an unrecognized algorithm prefix.
+fn sri_digest(alg: &str, bytes: &[u8]) -> Option<String> {
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
 
-    if let Err(e) = parsed {
-        println!("[ERROR] {}", e);
+    let digest = match alg {
+        "sha512" => Sha512::digest(bytes).to_vec(),
+        "sha256" => Sha256::digest(bytes).to_vec(),
+        "sha1" => Sha1::digest(bytes).to_vec(),
+        _ => return None,
+    };
 
-        return ExitCode::FAILURE;
+    Some(STANDARD.encode(digest))
+}
+
+/// Fetch the bytes a `resolved` URL points at.
+///
+/// Only `file:` and `http(s):` URLs are understood; anything else is an
+/// error so it can be surfaced as a distinct "fetch error" finding rather
+/// than a false "missing" report.
+fn fetch_bytes(resolved: &str) -> Result<Vec<u8>, String> {
+    if let Some(path) = resolved.strip_prefix("file:") {
+        return std::fs::read(path).map_err(|e| e.to_string());
     }
 
-    let v = parsed.unwrap();
+    if resolved.starts_with("http://") || resolved.starts_with("https://") {
+        let resp = ureq::get(resolved).call().map_err(|e| e.to_string())?;
+        let mut buf = Vec::new();
+        resp.into_reader()
+            .read_to_end(&mut buf)
+            .map_err(|e| e.to_string())?;
+        return Ok(buf);
+    }
 
-    let missing = validate_json(&v);
+    Err(format!("unsupported resolved URL scheme: {}", resolved))
+}
 
-    if missing.len() > 0 {
-        if let Some(name) = &v["name"].as_str() {
-            println!("[ERROR] [{}] package-lock.json is missing the following resolved/integrity fields:", name);
+/// Re-verify every package's recorded `integrity` against its tarball.
+///
+/// Only the flat `packages` map is read; a lockfileVersion 1 `dependencies`
+/// tree yields no findings. Downloads are cached by URL so shared dependencies are fetched once.
+fn verify_integrity(json: &Value, scope: Option<&str>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut cache: HashMap<String, Result<Vec<u8>, String>> = HashMap::new();
+
+    let packages = match json["packages"].as_object() {
+        Some(p) => p,
+        None => return findings,
+    };
+
+    for (k, v) in packages {
+        if k.is_empty() {
+            continue;
+        }
+
+        let in_scope = match scope {
+            Some(dir) => k.starts_with(&format!("{}/node_modules", dir)),
+            None => k.starts_with("node_modules"),
+        };
+        if !in_scope || v["link"] == Value::Bool(true) {
+            continue;
+        }
+
+        // only packages carrying both fields can be verified
+        let (resolved, integrity) = match (v["resolved"].as_str(), v["integrity"].as_str()) {
+            (Some(r), Some(i)) => (r, i),
+            _ => continue,
+        };
+
+        // npm may record several space-separated hashes; the first is enough
+        let recorded = integrity.split_whitespace().next().unwrap_or(integrity);
+        let (alg, expected) = match recorded.split_once('-') {
+            Some(parts) => parts,
+            None => {
+                findings.push(Finding {
+                    package: k.to_string(),
+                    kind: FindingKind::FetchError {
+                        reason: format!("malformed integrity value: {}", recorded),
+                    },
+                    severity: "error",
+                });
+                continue;
+            }
+        };
+
+        let bytes = cache
+            .entry(resolved.to_string())
+            .or_insert_with(|| fetch_bytes(resolved));
+
+        let bytes = match bytes {
+            Ok(b) => b,
+            Err(e) => {
+                findings.push(Finding {
+                    package: k.to_string(),
+                    kind: FindingKind::FetchError { reason: e.clone() },
+                    severity: "error",
+                });
+                continue;
+            }
+        };
+
+        match sri_digest(alg, bytes) {
+            Some(actual) => {
+                if !ct_eq(actual.as_bytes(), expected.as_bytes()) {
+                    findings.push(Finding {
+                        package: k.to_string(),
+                        kind: FindingKind::IntegrityMismatch {
+                            expected: recorded.to_string(),
+                            actual: format!("{}-{}", alg, actual),
+                        },
+                        severity: "error",
+                    });
+                }
+            }
+            None => findings.push(Finding {
+                package: k.to_string(),
+                kind: FindingKind::FetchError {
+                    reason: format!("unsupported integrity algorithm: {}", alg),
+                },
+                severity: "error",
+            }),
+        }
+    }
+
+    findings
+}
+
+/// The findings produced for a single discovered lock file.
+struct FileReport {
+    lockfile: PathBuf,
+    name: Option<String>,
+    findings: Vec<Finding>,
+}
+
+/// Render the aggregate report for every scanned lock file.
+///
+/// The `json` and `sarif` formats emit a versioned document so downstream
+/// parsers can detect schema changes, mirroring `cargo metadata`'s contract.
+fn render(reports: &[FileReport], format: Format) -> String {
+    match format {
+        Format::Text => render_text(reports),
+        Format::Json => render_json(reports),
+        Format::Sarif => render_sarif(reports),
+    }
+}
+
+fn render_text(reports: &[FileReport]) -> String {
+    let mut out = String::new();
+
+    for report in reports {
+        if report.findings.is_empty() {
+            continue;
+        }
+
+        // keep the original summary wording when every finding is a missing
+        // field; the header names each lock file by the path it was found at
+        let all_missing = report
+            .findings
+            .iter()
+            .all(|f| matches!(f.kind, FindingKind::Missing(_)));
+        let summary = if all_missing {
+            "is missing the following resolved/integrity fields:"
         } else {
-            println!(
-                "[ERROR] package-lock.json is missing the following resolved/integrity fields:"
-            );
+            "has the following findings:"
+        };
+
+        match &report.name {
+            Some(name) => out.push_str(&format!("[ERROR] [{}] {} {}\n", name, report.lockfile.display(), summary)),
+            None => out.push_str(&format!("[ERROR] {} {}\n", report.lockfile.display(), summary)),
         }
 
-        missing.iter().for_each(|m| {
-            println!("    {}", m);
-        });
+        for f in &report.findings {
+            match &f.kind {
+                FindingKind::Missing(_) => out.push_str(&format!("    {}\n", f.package)),
+                FindingKind::IntegrityMismatch { expected, actual } => out.push_str(&format!(
+                    "    {} [integrity mismatch] expected {} got {}\n",
+                    f.package, expected, actual
+                )),
+                FindingKind::FetchError { reason } => out.push_str(&format!(
+                    "    {} [fetch error] {}\n",
+                    f.package, reason
+                )),
+                FindingKind::RuleViolation { rule } => out.push_str(&format!(
+                    "    {} [rule: {}]\n",
+                    f.package, rule
+                )),
+            }
+        }
+    }
+
+    out
+}
 
-        return ExitCode::FAILURE;
+/// Build the JSON object for a single finding.
+fn finding_json(f: &Finding) -> Value {
+    let mut obj = json!({
+        "package": f.package,
+        "category": f.kind.category(),
+        "severity": f.severity,
+    });
+    match &f.kind {
+        FindingKind::Missing(m) => obj["missing"] = json!(m.fields()),
+        FindingKind::IntegrityMismatch { expected, actual } => {
+            obj["expected"] = json!(expected);
+            obj["actual"] = json!(actual);
+        }
+        FindingKind::FetchError { reason } => obj["reason"] = json!(reason),
+        FindingKind::RuleViolation { rule } => obj["rule"] = json!(rule),
     }
+    obj
+}
+
+fn render_json(reports: &[FileReport]) -> String {
+    let files: Vec<Value> = reports
+        .iter()
+        .map(|r| {
+            json!({
+                "lockfile": r.lockfile.display().to_string(),
+                "name": r.name,
+                "findings": r.findings.iter().map(finding_json).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    let report = json!({
+        "version": 1,
+        "files": files,
+    });
+
+    report.to_string()
+}
+
+/// Build the SARIF result object for a single finding at `uri`.
+fn finding_sarif(f: &Finding, uri: &str) -> Value {
+    let (rule_id, text) = match &f.kind {
+        FindingKind::Missing(m) => (
+            "missing-field",
+            format!("{} is missing {}", f.package, m.fields().join(" and ")),
+        ),
+        FindingKind::IntegrityMismatch { expected, actual } => (
+            "integrity-mismatch",
+            format!("{} integrity mismatch: expected {} got {}", f.package, expected, actual),
+        ),
+        FindingKind::FetchError { reason } => (
+            "fetch-error",
+            format!("{} could not be fetched: {}", f.package, reason),
+        ),
+        FindingKind::RuleViolation { rule } => (
+            "rule-violation",
+            format!("{} violates rule {}", f.package, rule),
+        ),
+    };
+    json!({
+        "ruleId": rule_id,
+        "level": f.severity,
+        "message": { "text": text },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri },
+            },
+        }],
+    })
+}
+
+fn render_sarif(reports: &[FileReport]) -> String {
+    let results: Vec<Value> = reports
+        .iter()
+        .flat_map(|r| {
+            let uri = r.lockfile.display().to_string();
+            r.findings.iter().map(move |f| finding_sarif(f, &uri))
+        })
+        .collect();
+
+    let sarif = json!({
+        "version": "2.1.0",
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "rust-pkg-lint",
+                    "rules": [
+                        { "id": "missing-field" },
+                        { "id": "integrity-mismatch" },
+                        { "id": "fetch-error" },
+                        { "id": "rule-violation" },
+                    ],
+                },
+            },
+            "results": results,
+        }],
+    });
 
-    ExitCode::SUCCESS
+    sarif.to_string()
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().collect::<Vec<String>>();
+
+    let mut format = Format::Text;
+    let mut workspace: Option<String> = None;
+    let mut verify = false;
+    let mut skip_dev = false;
+    let mut rules_file: Option<String> = None;
+    let mut recursive = false;
+    let mut quiet = false;
+    let mut allow_missing = false;
+    let mut roots: Vec<String> = Vec::new();
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" => {
+                i += 1;
+                match args.get(i).and_then(|f| Format::parse(f)) {
+                    Some(f) => format = f,
+                    None => {
+                        println!("[ERROR] --format expects one of text|json|sarif");
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--workspace" => {
+                i += 1;
+                match args.get(i) {
+                    Some(m) => workspace = Some(m.clone()),
+                    None => {
+                        println!("[ERROR] --workspace expects a member name");
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--verify-integrity" => verify = true,
+            "--skip-dev" => skip_dev = true,
+            "--rules" => {
+                i += 1;
+                match args.get(i) {
+                    Some(f) => rules_file = Some(f.clone()),
+                    None => {
+                        println!("[ERROR] --rules expects a config file path");
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "--recursive" => recursive = true,
+            "--quiet" => quiet = true,
+            "--allow-missing-lockfile" => allow_missing = true,
+            "--error-on-empty" => allow_missing = false,
+            arg if !arg.starts_with('-') => roots.push(arg.to_string()),
+            arg => {
+                println!("[ERROR] unknown argument {}", arg);
+                return ExitCode::FAILURE;
+            }
+        }
+        i += 1;
+    }
+
+    if roots.is_empty() {
+        roots.push(".".to_string());
+    }
+
+    // compile a user ruleset once, if one was supplied; otherwise each file
+    // falls back to the built-in resolved/integrity check
+    let rules = match &rules_file {
+        Some(path) => match load_rules(path) {
+            Ok(r) => Some(r),
+            Err(e) => {
+                println!("[ERROR] {}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+
+    let mut reports: Vec<FileReport> = Vec::new();
+
+    for root in &roots {
+        let found = discover(root, recursive);
+
+        if found.is_empty() {
+            let path = Path::new(root).join("package-lock.json");
+            if allow_missing {
+                if !quiet {
+                    println!("[WARN] no package-lock.json found at {}", path.display());
+                }
+                continue;
+            }
+            println!("[ERROR] Could not read package-lock.json at {}", path.display());
+            return ExitCode::FAILURE;
+        }
+
+        for lockfile in found {
+            let data = match read_to_string(&lockfile) {
+                Ok(d) => d,
+                Err(_) => {
+                    if allow_missing {
+                        continue;
+                    }
+                    println!("[ERROR] Could not read package-lock.json at {}", lockfile.display());
+                    return ExitCode::FAILURE;
+                }
+            };
+
+            let v = match parse_json(data) {
+                Ok(v) => v,
+                Err(e) => {
+                    println!("[ERROR] {}", e);
+                    return ExitCode::FAILURE;
+                }
+            };
+
+            // resolve the workspace member to a directory prefix, if requested
+            let scope = match &workspace {
+                Some(member) => match find_member_dir(&v, member) {
+                    Some(dir) => Some(dir),
+                    None => {
+                        println!(
+                            "[ERROR] no workspace member named {} in {}",
+                            member,
+                            lockfile.display()
+                        );
+                        return ExitCode::FAILURE;
+                    }
+                },
+                None => None,
+            };
+
+            let mut findings = match &rules {
+                Some(r) => apply_rules(&v, r),
+                None => validate_json(&v, scope.as_deref(), skip_dev),
+            };
+
+            if verify {
+                findings.extend(verify_integrity(&v, scope.as_deref()));
+            }
+
+            reports.push(FileReport {
+                lockfile,
+                name: v["name"].as_str().map(|s| s.to_string()),
+                findings,
+            });
+        }
+    }
+
+    let any = reports.iter().any(|r| !r.findings.is_empty());
+
+    if !quiet {
+        match format {
+            // human output stays silent on success, matching the original tool
+            Format::Text => {
+                if any {
+                    print!("{}", render(&reports, format));
+                }
+            }
+            // machine formats always emit the document so it can be piped
+            _ => println!("{}", render(&reports, format)),
+        }
+    }
+
+    if any {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
 
 #[cfg(test)]
@@ -121,7 +1019,7 @@ mod tests {
     fn test_good_json() {
         for lockfile in GOOD_LOCKS.iter() {
             let json = parse_json(lockfile.to_string()).unwrap();
-            let missing = validate_json(&json);
+            let missing = validate_json(&json, None, false);
 
             assert_eq!(dbg!(missing).len(), 0);
         }
@@ -131,9 +1029,195 @@ mod tests {
     fn test_bad_json() {
         for (i, lockfile) in BAD_LOCKS.iter().enumerate() {
             let json = parse_json(lockfile.to_string()).unwrap();
-            let missing = validate_json(&json);
+            let missing = validate_json(&json, None, false);
 
             assert_ne!(missing.len(), 0, "{}", i);
         }
     }
+
+    #[test]
+    fn test_json_report_is_versioned() {
+        let json = parse_json(BLK2.to_string()).unwrap();
+        let findings = validate_json(&json, None, false);
+        let reports = vec![FileReport {
+            lockfile: PathBuf::from("package-lock.json"),
+            name: None,
+            findings,
+        }];
+        let out = render(&reports, Format::Json);
+        let parsed: Value = from_str(&out).unwrap();
+
+        assert_eq!(parsed["version"], json!(1));
+        assert!(!parsed["files"][0]["findings"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let segs = parse_path("$.packages[*].integrity").unwrap();
+        assert!(matches!(segs[0], Seg::Key(ref k) if k == "packages"));
+        assert!(matches!(segs[1], Seg::Wildcard));
+        assert!(matches!(segs[2], Seg::Key(ref k) if k == "integrity"));
+
+        assert!(parse_path("packages").is_err());
+        assert!(matches!(parse_path("$[0]").unwrap()[0], Seg::Index(0)));
+    }
+
+    #[test]
+    fn test_eval_path_skips_self_entry() {
+        // the `""` self key must not be matched by a wildcard
+        let v: Value = from_str(
+            r#"{ "packages": { "": { "name": "root" }, "node_modules/a": { "integrity": "x" } } }"#,
+        )
+        .unwrap();
+
+        let matched = eval_path(&v, &parse_path("$.packages[*]").unwrap());
+        let paths: Vec<&str> = matched.iter().map(|(p, _)| p.as_str()).collect();
+
+        assert_eq!(paths, vec!["$.packages.node_modules/a"]);
+    }
+
+    #[test]
+    fn test_apply_rules_exists_and_matches() {
+        let v: Value = from_str(
+            r#"{ "packages": {
+                "": {},
+                "node_modules/a": { "resolved": "https://registry.npmjs.org/a", "integrity": "x" },
+                "node_modules/b": { "resolved": "https://evil.example/b" }
+            } }"#,
+        )
+        .unwrap();
+
+        let rules = vec![
+            Rule {
+                name: "require-integrity".to_string(),
+                segs: parse_path("$.packages[*].integrity").unwrap(),
+                assertion: Assertion::Exists,
+            },
+            Rule {
+                name: "no-private-registry".to_string(),
+                segs: parse_path("$.packages[*].resolved").unwrap(),
+                assertion: Assertion::Matches(Regex::new("^https://registry").unwrap()),
+            },
+        ];
+
+        let findings = apply_rules(&v, &rules);
+
+        // self entry is exempt; `b` is missing integrity and off-registry
+        assert_eq!(findings.len(), 2);
+        assert!(findings
+            .iter()
+            .all(|f| f.package.contains("node_modules/b")));
+    }
+
+    #[test]
+    fn test_find_member_dir() {
+        let v: Value = from_str(
+            r#"{ "packages": {
+                "": { "name": "root" },
+                "packages/foo": { "name": "foo" },
+                "node_modules/foo": { "link": true, "resolved": "packages/foo" }
+            } }"#,
+        )
+        .unwrap();
+
+        // the symlink's resolved path wins
+        assert_eq!(find_member_dir(&v, "foo").as_deref(), Some("packages/foo"));
+        // the path key can be given directly
+        assert_eq!(find_member_dir(&v, "packages/foo").as_deref(), Some("packages/foo"));
+        assert_eq!(find_member_dir(&v, "missing"), None);
+    }
+
+    #[test]
+    fn test_validate_packages_scope() {
+        // only deps nested under the member prefix are in scope
+        let v: Value = from_str(
+            r#"{ "packages": {
+                "": {},
+                "packages/foo/node_modules/dep-a": { "version": "1.0.0" },
+                "node_modules/dep-b": { "version": "2.0.0" }
+            } }"#,
+        )
+        .unwrap();
+
+        let scoped = validate_packages(&v, Some("packages/foo"));
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].package, "packages/foo/node_modules/dep-a");
+    }
+
+    #[test]
+    fn test_sri_digest() {
+        // known base64 SRI digests of b"abc"
+        assert_eq!(
+            sri_digest("sha512", b"abc").unwrap(),
+            "3a81oZNherrMQXNJriBBMRLm+k6JqX6iCp7u5ktV05ohkpkqJ0/BqDa6PCOj/uu9RU1EI2Q86A4qmslPpUyknw=="
+        );
+        assert_eq!(
+            sri_digest("sha256", b"abc").unwrap(),
+            "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="
+        );
+        assert_eq!(
+            sri_digest("sha1", b"abc").unwrap(),
+            "qZk+NkcGgWq6PiVxeFDCbJzQ2J0="
+        );
+        assert!(sri_digest("md5", b"abc").is_none());
+    }
+
+    #[test]
+    fn test_validate_dependencies_dotted_path() {
+        let v: Value = from_str(
+            r#"{ "lockfileVersion": 1, "name": "old", "dependencies": {
+                "foo": {
+                    "version": "1.0.0", "resolved": "https://r/foo", "integrity": "sha1-a",
+                    "dependencies": { "bar": { "version": "2.0.0" } }
+                }
+            } }"#,
+        )
+        .unwrap();
+
+        let findings = validate_json(&v, None, false);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].package, "foo > bar");
+    }
+
+    #[test]
+    fn test_validate_dependencies_skips_bundled_and_dev() {
+        let v: Value = from_str(
+            r#"{ "lockfileVersion": 1, "dependencies": {
+                "bundled-x": { "version": "1.0.0", "bundled": true },
+                "dev-y": { "version": "2.0.0", "dev": true }
+            } }"#,
+        )
+        .unwrap();
+
+        // bundled is always skipped; dev only when skip_dev is set
+        assert_eq!(validate_json(&v, None, true).len(), 0);
+        assert_eq!(validate_json(&v, None, false).len(), 1);
+    }
+
+    #[test]
+    fn test_discover_recursive_skips_node_modules() {
+        use std::fs;
+
+        let root = std::env::temp_dir().join(format!("pkglint-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in ["", "sub", "node_modules/dep"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("package-lock.json"), "{}").unwrap();
+        }
+        let root = root.to_str().unwrap();
+
+        // non-recursive sees only the root lock file
+        assert_eq!(discover(root, false).len(), 1);
+
+        // recursive descends into sub/ but never node_modules/
+        let mut found: Vec<String> = discover(root, true)
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+        found.sort();
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|p| !p.contains("node_modules")));
+
+        let _ = fs::remove_dir_all(root);
+    }
 }